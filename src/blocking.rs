@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use crate::product::Product;
+
+/// Generates the character q-grams of `text`. Operates on `chars()` rather
+/// than bytes so multi-byte UTF-8 sequences are never split apart.
+///
+/// Names shorter than `q` have no q-grams of that length, so the whole
+/// string is returned as a single token instead.
+pub fn qgrams(text: &str, q: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() < q {
+        return vec![text.to_string()];
+    }
+
+    chars
+        .windows(q)
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+/// An inverted index from q-gram to the indices of the settings-bearing
+/// products whose name produces it, used to shrink the all-pairs scan in
+/// `calculate` down to plausible candidates only.
+pub struct QgramIndex {
+    q: usize,
+    index: HashMap<String, Vec<usize>>,
+}
+
+impl QgramIndex {
+    pub fn build(products_with_settings: &[&Product], q: usize) -> Self {
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for (i, product) in products_with_settings.iter().enumerate() {
+            for qgram in qgrams(&product.name, q) {
+                index.entry(qgram).or_default().push(i);
+            }
+        }
+
+        Self { q, index }
+    }
+
+    /// Returns the de-duplicated indices of candidates sharing at least
+    /// `min_overlap` q-grams with `name`.
+    pub fn candidates(&self, name: &str, min_overlap: usize) -> Vec<usize> {
+        let mut overlap_counts: HashMap<usize, usize> = HashMap::new();
+
+        for qgram in qgrams(name, self.q) {
+            if let Some(indices) = self.index.get(&qgram) {
+                for &i in indices {
+                    *overlap_counts.entry(i).or_insert(0) += 1;
+                }
+            }
+        }
+
+        overlap_counts
+            .into_iter()
+            .filter(|&(_, count)| count >= min_overlap)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
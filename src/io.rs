@@ -0,0 +1,85 @@
+use anyhow::Context;
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// Something `Product`/`Settings` can be loaded from.
+pub trait Source {
+    fn read_to_string(&self) -> anyhow::Result<String>;
+}
+
+/// Something results can be written to.
+pub trait Sink {
+    fn write(&self, contents: &str) -> anyhow::Result<()>;
+}
+
+/// Reads from and writes to a file on disk.
+pub struct FileIo {
+    path: PathBuf,
+}
+
+impl FileIo {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Source for FileIo {
+    fn read_to_string(&self) -> anyhow::Result<String> {
+        let mut file = File::open(&self.path).context("Open file.")?;
+
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).context("Read file.")?;
+
+        Ok(contents)
+    }
+}
+
+impl Sink for FileIo {
+    fn write(&self, contents: &str) -> anyhow::Result<()> {
+        let mut file = File::create(&self.path).context("Create file.")?;
+        file.write_all(contents.as_bytes()).context("Write file.")?;
+
+        Ok(())
+    }
+}
+
+/// An in-memory `Source`/`Sink`, backed by a shared buffer instead of the
+/// filesystem. Lets callers feed products/settings from any upstream
+/// (a database row, an HTTP body, a test fixture) without touching disk.
+#[derive(Default, Clone)]
+pub struct MemoryIo {
+    contents: Arc<Mutex<String>>,
+}
+
+impl MemoryIo {
+    pub fn new(contents: impl Into<String>) -> Self {
+        Self {
+            contents: Arc::new(Mutex::new(contents.into())),
+        }
+    }
+}
+
+impl Source for MemoryIo {
+    fn read_to_string(&self) -> anyhow::Result<String> {
+        Ok(self
+            .contents
+            .lock()
+            .expect("Lock is already held by the current thread.")
+            .clone())
+    }
+}
+
+impl Sink for MemoryIo {
+    fn write(&self, contents: &str) -> anyhow::Result<()> {
+        *self
+            .contents
+            .lock()
+            .expect("Lock is already held by the current thread.") = contents.to_string();
+
+        Ok(())
+    }
+}
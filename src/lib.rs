@@ -0,0 +1,477 @@
+use anyhow::ensure;
+use product::{Match, Product};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use settings::SimilarityType;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{blocking::QgramIndex, settings::Settings, tokens::IdfTable};
+
+pub mod blocking;
+pub mod format;
+pub mod io;
+pub mod output;
+pub mod product;
+pub mod settings;
+pub mod tokens;
+
+/// Library entry point: matches every settings-less product in `products`
+/// against the settings-bearing ones using the enabled similarity types,
+/// and returns one output record per settings-less product with its hits
+/// ranked by score. Only settings-bearing products sharing at least
+/// `qgram_min_overlap` q-grams with the name are compared; names shorter
+/// than `qgram_size` are compared against all of them. Takes already-loaded
+/// products rather than a backend, so embedders can load them through any
+/// `Source` (a database, an HTTP body) instead of a fixed file on disk.
+pub fn calculate(settings: Settings, products: Vec<Product>) -> anyhow::Result<Vec<Product>> {
+    let products_without_settings: Vec<&Product> = products
+        .iter()
+        .filter(|product| product.settings_id.is_empty())
+        .collect();
+
+    ensure!(
+        !products_without_settings.is_empty(),
+        "Products without settings not found."
+    );
+
+    let products_with_settings: Vec<&Product> = products
+        .iter()
+        .filter(|product| !product.settings_id.is_empty())
+        .collect();
+
+    ensure!(
+        !products_with_settings.is_empty(),
+        "Products with settings not found."
+    );
+
+    tracing::info!(
+        "Products: {} / {}",
+        products_with_settings.len(),
+        products_without_settings.len(),
+    );
+
+    let qgram_index = QgramIndex::build(&products_with_settings, settings.qgram_size);
+
+    // TF-IDF needs corpus-wide document frequencies, so it's precomputed
+    // once here rather than per pair in the loop below.
+    let idf_table = settings
+        .similarities_types
+        .iter()
+        .any(|similarity_type| matches!(similarity_type, SimilarityType::TfidfCosine(_)))
+        .then(|| IdfTable::build(products.iter().map(|product| product.name.as_str())));
+
+    let matches = Arc::new(Mutex::new(Vec::<(String, Match)>::new()));
+
+    products_without_settings
+        .par_iter()
+        .for_each(|product_without_settings| {
+            let candidate_indices: Vec<usize> = if product_without_settings.name.chars().count()
+                < settings.qgram_size
+            {
+                (0..products_with_settings.len()).collect()
+            } else {
+                qgram_index.candidates(&product_without_settings.name, settings.qgram_min_overlap)
+            };
+
+            candidate_indices.par_iter().for_each(|&candidate_index| {
+                let product_with_settings = products_with_settings[candidate_index];
+                settings
+                    .similarities_types
+                    .par_iter()
+                    .for_each(|similarity_type| match *similarity_type {
+                        SimilarityType::Hamming { max, normalize } => {
+                            if let Ok(distance) = strsim::hamming(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            ) {
+                                let comparison_distance = comparison_distance(
+                                    distance,
+                                    &product_without_settings.name,
+                                    &product_with_settings.name,
+                                    normalize,
+                                );
+
+                                if comparison_distance <= max {
+                                    let mut matches = matches
+                                        .lock()
+                                        .expect("Lock is already held by the current thread.");
+
+                                    matches.push((
+                                        product_without_settings.id.clone(),
+                                        Match::new_with_f64_similarity(
+                                            product_with_settings,
+                                            similarity_type,
+                                            normalized_similarity(
+                                                distance,
+                                                &product_without_settings.name,
+                                                &product_with_settings.name,
+                                            ),
+                                        ),
+                                    ));
+                                }
+                            }
+                        }
+                        SimilarityType::Levenshtein { max, normalize } => {
+                            let distance = strsim::levenshtein(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            );
+
+                            let comparison_distance = comparison_distance(
+                                distance,
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                                normalize,
+                            );
+
+                            if comparison_distance <= max {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        normalized_similarity(
+                                            distance,
+                                            &product_without_settings.name,
+                                            &product_with_settings.name,
+                                        ),
+                                    ),
+                                ));
+                            }
+                        }
+                        SimilarityType::NormalizedLevenshtein(min) => {
+                            let similarity = strsim::normalized_levenshtein(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            );
+
+                            if min < similarity {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        similarity,
+                                    ),
+                                ));
+                            }
+                        }
+                        SimilarityType::OsaDistance { max, normalize } => {
+                            let distance = strsim::osa_distance(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            );
+
+                            let comparison_distance = comparison_distance(
+                                distance,
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                                normalize,
+                            );
+
+                            if comparison_distance <= max {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        normalized_similarity(
+                                            distance,
+                                            &product_without_settings.name,
+                                            &product_with_settings.name,
+                                        ),
+                                    ),
+                                ));
+                            }
+                        }
+                        SimilarityType::DamerauLevenshtein { max, normalize } => {
+                            let distance = strsim::damerau_levenshtein(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            );
+
+                            let comparison_distance = comparison_distance(
+                                distance,
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                                normalize,
+                            );
+
+                            if comparison_distance <= max {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        normalized_similarity(
+                                            distance,
+                                            &product_without_settings.name,
+                                            &product_with_settings.name,
+                                        ),
+                                    ),
+                                ));
+                            }
+                        }
+                        SimilarityType::NormalizedDamerauLevenshtein(min) => {
+                            let similarity = strsim::normalized_damerau_levenshtein(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            );
+
+                            if min < similarity {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        similarity,
+                                    ),
+                                ));
+                            }
+                        }
+                        SimilarityType::Jaro(min) => {
+                            let similarity = strsim::jaro(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            );
+
+                            if min < similarity {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        similarity,
+                                    ),
+                                ));
+                            }
+                        }
+                        SimilarityType::JaroWinkler(min) => {
+                            let similarity = strsim::jaro_winkler(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            );
+
+                            if min < similarity {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        similarity,
+                                    ),
+                                ));
+                            }
+                        }
+                        SimilarityType::SorensenDice(min) => {
+                            let similarity = strsim::sorensen_dice(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            );
+
+                            if min < similarity {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        similarity,
+                                    ),
+                                ));
+                            }
+                        }
+                        SimilarityType::TokenJaccard(min) => {
+                            let similarity = tokens::jaccard(
+                                &product_without_settings.name,
+                                &product_with_settings.name,
+                            );
+
+                            if min < similarity {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        similarity,
+                                    ),
+                                ));
+                            }
+                        }
+                        SimilarityType::TfidfCosine(min) => {
+                            let similarity = idf_table
+                                .as_ref()
+                                .expect("IDF table is built whenever TfidfCosine is enabled.")
+                                .cosine(
+                                    &product_without_settings.name,
+                                    &product_with_settings.name,
+                                );
+
+                            if min < similarity {
+                                let mut matches = matches
+                                    .lock()
+                                    .expect("Lock is already held by the current thread.");
+
+                                matches.push((
+                                    product_without_settings.id.clone(),
+                                    Match::new_with_f64_similarity(
+                                        product_with_settings,
+                                        similarity_type,
+                                        similarity,
+                                    ),
+                                ));
+                            }
+                        }
+                    });
+            });
+        });
+
+    let matches = Arc::try_unwrap(matches)
+        .expect("Error on Arc::try_unwrap matches.")
+        .into_inner()
+        .expect("Error on Arc::try_unwrap::into_inner matches.");
+
+    let products_with_settings_by_id: HashMap<&str, &Product> = products_with_settings
+        .iter()
+        .map(|product| (product.id.as_str(), *product))
+        .collect();
+
+    let mut matches_by_product: HashMap<String, Vec<Match>> = HashMap::new();
+    for (product_id, product_match) in matches {
+        matches_by_product
+            .entry(product_id)
+            .or_default()
+            .push(product_match);
+    }
+
+    let products = products_without_settings
+        .iter()
+        .map(|product| {
+            // Products with no surviving candidate still get an output
+            // record (with empty matches/settings_id), per this function's
+            // "one output record per settings-less product" contract.
+            let mut product_matches = matches_by_product.remove(&product.id).unwrap_or_default();
+            // `Match::score` is direction-normalized (higher is always more
+            // similar, see its doc comment), so ranking and picking the
+            // settings_id off the front of this sort is meaningful across
+            // metric types.
+            product_matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+            let settings_id = product_matches
+                .first()
+                .and_then(|best_match| {
+                    products_with_settings_by_id.get(best_match.product_id.as_str())
+                })
+                .map(|product_with_settings| product_with_settings.settings_id.clone())
+                .unwrap_or_default();
+
+            product.with_matches(product_matches, settings_id)
+        })
+        .collect();
+
+    Ok(products)
+}
+
+/// Converts a raw edit distance into the value actually compared against a
+/// `max` threshold: the distance itself, or that distance divided by the
+/// longer of the two names' character length when `normalize` is set.
+fn comparison_distance(distance: usize, a: &str, b: &str, normalize: bool) -> f64 {
+    if !normalize {
+        return distance as f64;
+    }
+
+    let longer_len = a.chars().count().max(b.chars().count()).max(1);
+    distance as f64 / longer_len as f64
+}
+
+/// Converts a raw edit distance into a `[0, 1]` similarity score: `1.0`
+/// minus the distance divided by the longer of the two names' character
+/// length. Always length-normalized regardless of the threshold's
+/// `normalize` setting, so a `Match::score` stored for a distance metric
+/// stays comparable to the native `[0, 1]` scores the other metrics store.
+fn normalized_similarity(distance: usize, a: &str, b: &str) -> f64 {
+    let longer_len = a.chars().count().max(b.chars().count()).max(1);
+    1.0 - (distance as f64 / longer_len as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        format::Format,
+        io::{MemoryIo, Source},
+    };
+
+    #[test]
+    fn calculate_picks_the_closer_candidate_via_memory_io() {
+        let products_json = r#"[
+            {"id": "1", "name": "Apple iPhone 13 128GB", "settings_id": [], "matches": []},
+            {"id": "2", "name": "Apple iPhone 13 128GB", "settings_id": ["close"], "matches": []},
+            {"id": "3", "name": "Apple iPhone 13 256GB", "settings_id": ["far"], "matches": []}
+        ]"#;
+
+        let source = MemoryIo::new(products_json);
+        let products = Product::load(&source, Format::Json).expect("load products");
+
+        let settings = Settings {
+            similarities_types: vec![SimilarityType::Levenshtein {
+                max: 10.0,
+                normalize: false,
+            }],
+            ..Settings::default()
+        };
+
+        let calculated = calculate(settings, products).expect("calculate");
+        assert_eq!(calculated.len(), 1);
+
+        let result = &calculated[0];
+        // "2" is an exact-name match (distance 0), "3" differs by three
+        // characters, so the closer candidate must win the ranking and be
+        // the one the output's settings_id is drawn from.
+        assert_eq!(result.settings_id, vec!["close".to_string()]);
+        assert_eq!(result.matches[0].product_id, "2");
+        assert!(result.matches[0].score > result.matches[1].score);
+
+        let sink = MemoryIo::default();
+        output::write_output(&sink, calculated, Format::Json).expect("write output");
+        let written = sink.read_to_string().expect("read output sink");
+        assert!(written.contains("\"close\""));
+    }
+}
@@ -1,16 +1,18 @@
 use anyhow::Context;
-use std::path::Path;
 
-use crate::product::Product;
+use crate::{format::Format, io::Sink, product::Product};
 
-const OUTPUT_FILE: &'static str = "output.json";
+pub const BASE_NAME: &str = "output";
 
-pub(crate) fn write_output(products: Vec<Product>) -> anyhow::Result<()> {
-    let serialized_settings =
-        serde_json::to_string_pretty(&products).context("Serialize output file.")?;
+pub fn file_name(format: Format) -> String {
+    format!("{}.{}", BASE_NAME, format.extension())
+}
+
+pub fn write_output(sink: &dyn Sink, products: Vec<Product>, format: Format) -> anyhow::Result<()> {
+    let serialized_products = Product::serialize(&products, format)?;
 
-    let path = Path::new(OUTPUT_FILE);
-    std::fs::write(path, serialized_settings.as_bytes()).context("Write output file.")?;
+    sink.write(&serialized_products)
+        .context("Write output file.")?;
 
     Ok(())
 }
@@ -1,94 +1,201 @@
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::File,
-    hash::Hash,
-    io::{Read, Write},
-    path::Path,
-};
+use std::{hash::Hash, path::Path};
 
-use crate::settings::SimilarityType;
+use crate::{
+    format::Format,
+    io::{FileIo, Sink, Source},
+    settings::SimilarityType,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Product {
     pub id: String,
     pub name: String,
     pub settings_id: Vec<String>,
+    pub matches: Vec<Match>,
 }
 
-impl Product {
-    pub const FILE: &'static str = "products.json";
+/// A single candidate hit against a settings-bearing product, kept so the
+/// output can be audited: which product it matched, which metric fired, and
+/// what score it produced.
+///
+/// `score` is always a `[0, 1]` value where higher means more similar,
+/// regardless of metric: callers ranking or selecting among a product's
+/// matches (see `Product::with_matches`) can always sort by `score`
+/// descending. Edit-distance metrics store a length-normalized similarity
+/// (`1.0` minus the distance divided by the longer name's character length)
+/// rather than the raw distance, which is both unbounded and
+/// smaller-is-better, so scores stay comparable across every metric.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Match {
+    pub product_id: String,
+    pub similarity_type: SimilarityType,
+    pub score: f64,
+}
 
-    pub fn new_with_usize_similarity(
-        product_without_settings: &Product,
+impl Match {
+    pub fn new_with_f64_similarity(
         product_with_settings: &Product,
         similarity_type: &SimilarityType,
-        similarity: usize,
+        similarity: f64,
     ) -> Self {
         tracing::info!(
-            "Product [{:?}] -> [{:?}]: {:?} (Result: {:?})",
-            product_without_settings.name,
+            "Match [{:?}]: {:?} (Result: {:?})",
             product_with_settings.name,
             similarity_type,
             similarity
         );
 
         Self {
-            id: product_without_settings.id.clone(),
-            name: product_without_settings.name.clone(),
-            settings_id: product_with_settings.settings_id.clone(),
+            product_id: product_with_settings.id.clone(),
+            similarity_type: similarity_type.clone(),
+            score: similarity,
         }
     }
+}
 
-    pub fn new_with_f64_similarity(
-        product_without_settings: &Product,
-        product_with_settings: &Product,
-        similarity_type: &SimilarityType,
-        similarity: f64,
-    ) -> Self {
-        tracing::info!(
-            "Product [{:?}] -> [{:?}]: {:?} (Result: {:?})",
-            product_without_settings.name,
-            product_with_settings.name,
-            similarity_type,
-            similarity
-        );
+/// Sub-field separator used to pack a product's settings_id list and match
+/// list into single CSV cells.
+const CSV_LIST_SEPARATOR: &str = "|";
+
+impl Product {
+    const BASE_NAME: &'static str = "products";
 
+    /// Builds the output record for a settings-less product from its
+    /// matches, which callers are expected to have ranked by score
+    /// (highest first, see `Match::score`) and used to pick `settings_id`.
+    pub fn with_matches(&self, matches: Vec<Match>, settings_id: Vec<String>) -> Self {
         Self {
-            id: product_without_settings.id.clone(),
-            name: product_without_settings.name.clone(),
-            settings_id: product_with_settings.settings_id.clone(),
+            id: self.id.clone(),
+            name: self.name.clone(),
+            settings_id,
+            matches,
         }
     }
 
-    pub fn init() -> anyhow::Result<Vec<Product>> {
-        Self::create()?;
-        Self::load()
+    /// Convenience for the CLI binary: creates `products.<ext>` with an
+    /// empty catalog if it doesn't exist yet, then loads it from disk.
+    pub fn init(format: Format) -> anyhow::Result<Vec<Product>> {
+        let path = Self::file_name(format);
+        Self::create_file(&path, format)?;
+        Self::load(&FileIo::new(&path), format)
+    }
+
+    pub fn file_name(format: Format) -> String {
+        format!("{}.{}", Self::BASE_NAME, format.extension())
     }
 
-    fn create() -> anyhow::Result<()> {
-        let path = Path::new(Self::FILE);
-        if !Path::exists(path) {
-            let settings: Vec<Product> = vec![];
-            let serialized_settings =
-                serde_json::to_string_pretty(&settings).context("Serialize products file.")?;
+    fn create_file(path: &str, format: Format) -> anyhow::Result<()> {
+        if !Path::new(path).exists() {
+            let serialized_products = Self::serialize(&[], format)?;
 
-            let mut file = File::create(path).context("Create products file.")?;
-            file.write_all(serialized_settings.as_bytes())
-                .context("Write products file.")?;
+            FileIo::new(path)
+                .write(&serialized_products)
+                .context("Create products file.")?;
         }
 
         Ok(())
     }
 
-    fn load() -> anyhow::Result<Vec<Self>> {
-        let mut file = File::open(Self::FILE).context("Open products file.")?;
+    pub fn load(source: &dyn Source, format: Format) -> anyhow::Result<Vec<Self>> {
+        let contents = source.read_to_string().context("Read products source.")?;
+
+        match format {
+            Format::Json => Ok(serde_json::from_str(&contents).context("Deserialize products.")?),
+            Format::JsonLines => contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| serde_json::from_str(line).context("Deserialize product line."))
+                .collect(),
+            Format::Csv => Self::load_csv(&contents),
+        }
+    }
+
+    /// Expects a header row (`id,name,settings_id[,matches]`), matching what
+    /// `Self::serialize_csv` writes. The first row is always taken as the
+    /// header, so a header-less feed loses its first data row.
+    fn load_csv(contents: &str) -> anyhow::Result<Vec<Self>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(contents.as_bytes());
+
+        reader
+            .records()
+            .map(|record| {
+                let record = record.context("Read products CSV record.")?;
+
+                let id = record.get(0).context("Missing id column.")?.to_string();
+                let name = record.get(1).context("Missing name column.")?.to_string();
+                let settings_id = record
+                    .get(2)
+                    .unwrap_or_default()
+                    .split(CSV_LIST_SEPARATOR)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+                    .collect();
+
+                Ok(Self {
+                    id,
+                    name,
+                    settings_id,
+                    matches: vec![],
+                })
+            })
+            .collect()
+    }
+
+    pub fn serialize(products: &[Self], format: Format) -> anyhow::Result<String> {
+        match format {
+            Format::Json => {
+                Ok(serde_json::to_string_pretty(products).context("Serialize products file.")?)
+            }
+            Format::JsonLines => products
+                .iter()
+                .map(|product| serde_json::to_string(product).context("Serialize product line."))
+                .collect::<anyhow::Result<Vec<_>>>()
+                .map(|lines| lines.join("\n")),
+            Format::Csv => Self::serialize_csv(products),
+        }
+    }
 
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)
-            .context("Read products file.")?;
+    fn serialize_csv(products: &[Self]) -> anyhow::Result<String> {
+        let mut writer = csv::WriterBuilder::new().from_writer(vec![]);
+
+        writer
+            .write_record(["id", "name", "settings_id", "matches"])
+            .context("Write products CSV header.")?;
+
+        for product in products {
+            let settings_id = product.settings_id.join(CSV_LIST_SEPARATOR);
+            let matches = Match::join(&product.matches)?;
+
+            writer
+                .write_record([
+                    product.id.as_str(),
+                    product.name.as_str(),
+                    settings_id.as_str(),
+                    matches.as_str(),
+                ])
+                .context("Write products CSV record.")?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .context("Finalize products CSV writer.")?;
+        String::from_utf8(bytes).context("Products CSV output is not valid UTF-8.")
+    }
+}
 
-        Ok(serde_json::from_str(&contents).context("Serialize products file.")?)
+impl Match {
+    /// Packs a product's matches into a single CSV cell, one JSON object per
+    /// match separated by `CSV_LIST_SEPARATOR`.
+    fn join(matches: &[Match]) -> anyhow::Result<String> {
+        matches
+            .iter()
+            .map(|product_match| serde_json::to_string(product_match).context("Serialize match."))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(|parts| parts.join(CSV_LIST_SEPARATOR))
     }
 }
 
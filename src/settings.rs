@@ -1,48 +1,56 @@
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::File,
-    io::{Read, Write},
-    path::Path,
+use std::path::Path;
+
+use crate::{
+    format::Format,
+    io::{FileIo, Sink, Source},
 };
 
 #[derive(Serialize, Deserialize)]
 pub struct Settings {
     pub num_threads: usize,
     pub similarities_types: Vec<SimilarityType>,
+    /// Size (in characters) of the q-grams used to block candidate pairs
+    /// before the metric loop runs.
+    pub qgram_size: usize,
+    /// Minimum number of shared q-grams a candidate needs to be compared.
+    pub qgram_min_overlap: usize,
+    /// Format of the products catalog read from disk.
+    pub input_format: Format,
+    /// Format the matched products are written back in.
+    pub output_format: Format,
 }
 
 impl Settings {
     pub const FILE: &'static str = "settings.json";
 
+    /// Convenience for the CLI binary: creates `settings.json` with the
+    /// default settings if it doesn't exist yet, then loads it from disk.
     pub fn init() -> anyhow::Result<Self> {
-        Self::create()?;
-        Self::load()
+        Self::create_file()?;
+        Self::load(&FileIo::new(Self::FILE))
     }
 
-    fn create() -> anyhow::Result<()> {
+    fn create_file() -> anyhow::Result<()> {
         let path = Path::new(Self::FILE);
         if !Path::exists(path) {
             let settings = Self::default();
             let serialized_settings =
                 serde_json::to_string_pretty(&settings).context("Serialize settings.")?;
 
-            let mut file = File::create(path).context("Create settings file.")?;
-            file.write_all(serialized_settings.as_bytes())
-                .context("Write settings file.")?;
+            FileIo::new(Self::FILE)
+                .write(&serialized_settings)
+                .context("Create settings file.")?;
         }
 
         Ok(())
     }
 
-    fn load() -> anyhow::Result<Self> {
-        let mut file = File::open(Self::FILE).context("Open settings file.")?;
-
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)
-            .context("Read settings file.")?;
+    pub fn load(source: &dyn Source) -> anyhow::Result<Self> {
+        let contents = source.read_to_string().context("Read settings source.")?;
 
-        Ok(serde_json::from_str(&contents).context("Serialize settings file.")?)
+        Ok(serde_json::from_str(&contents).context("Deserialize settings.")?)
     }
 }
 
@@ -50,12 +58,28 @@ impl Default for Settings {
     fn default() -> Self {
         Self {
             num_threads: 2,
+            qgram_size: 3,
+            qgram_min_overlap: 1,
+            input_format: Format::Json,
+            output_format: Format::Json,
             similarities_types: vec![
-                SimilarityType::Hamming(100),
-                SimilarityType::Levenshtein(5),
+                SimilarityType::Hamming {
+                    max: 2.0,
+                    normalize: false,
+                },
+                SimilarityType::Levenshtein {
+                    max: 5.0,
+                    normalize: false,
+                },
                 SimilarityType::NormalizedLevenshtein(0.9),
-                SimilarityType::OsaDistance(100),
-                SimilarityType::DamerauLevenshtein(100),
+                SimilarityType::OsaDistance {
+                    max: 5.0,
+                    normalize: false,
+                },
+                SimilarityType::DamerauLevenshtein {
+                    max: 5.0,
+                    normalize: false,
+                },
                 SimilarityType::NormalizedDamerauLevenshtein(0.9),
                 SimilarityType::Jaro(0.9),
                 SimilarityType::JaroWinkler(0.9),
@@ -65,15 +89,34 @@ impl Default for Settings {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SimilarityType {
-    Hamming(usize),
-    Levenshtein(usize),
+    /// Edit-distance metrics keep a pair when its distance is at most
+    /// `max`. When `normalize` is set, the raw distance is divided by the
+    /// longer of the two names' character length before being compared, so
+    /// the same `max` is meaningful across name lengths.
+    Hamming {
+        max: f64,
+        normalize: bool,
+    },
+    Levenshtein {
+        max: f64,
+        normalize: bool,
+    },
+    OsaDistance {
+        max: f64,
+        normalize: bool,
+    },
+    DamerauLevenshtein {
+        max: f64,
+        normalize: bool,
+    },
+    /// Score-type metrics keep a pair when its score is above `min`.
     NormalizedLevenshtein(f64),
-    OsaDistance(usize),
-    DamerauLevenshtein(usize),
     NormalizedDamerauLevenshtein(f64),
     Jaro(f64),
     JaroWinkler(f64),
     SorensenDice(f64),
+    TokenJaccard(f64),
+    TfidfCosine(f64),
 }
@@ -0,0 +1,107 @@
+use std::collections::{HashMap, HashSet};
+
+/// Splits `text` into lowercase tokens on whitespace and punctuation, so
+/// word order and stray descriptors don't throw off token-based metrics.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+/// Jaccard similarity between the token sets of `a` and `b`: `|A∩B| / |A∪B|`.
+pub fn jaccard(a: &str, b: &str) -> f64 {
+    let tokens_a: HashSet<String> = tokenize(a).into_iter().collect();
+    let tokens_b: HashSet<String> = tokenize(b).into_iter().collect();
+
+    if tokens_a.is_empty() && tokens_b.is_empty() {
+        return 1.0;
+    }
+
+    let intersection = tokens_a.intersection(&tokens_b).count();
+    let union = tokens_a.union(&tokens_b).count();
+
+    intersection as f64 / union as f64
+}
+
+/// Corpus-wide inverse document frequency table, built once so `cosine` can
+/// weight tokens without recomputing document frequencies for every pair.
+pub struct IdfTable {
+    document_count: usize,
+    document_frequencies: HashMap<String, usize>,
+}
+
+impl IdfTable {
+    pub fn build<'a>(documents: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut document_count = 0;
+        let mut document_frequencies: HashMap<String, usize> = HashMap::new();
+
+        for document in documents {
+            document_count += 1;
+
+            let tokens: HashSet<String> = tokenize(document).into_iter().collect();
+            for token in tokens {
+                *document_frequencies.entry(token).or_insert(0) += 1;
+            }
+        }
+
+        Self {
+            document_count,
+            document_frequencies,
+        }
+    }
+
+    fn idf(&self, token: &str) -> f64 {
+        let document_frequency = self.document_frequencies.get(token).copied().unwrap_or(0);
+        ((self.document_count as f64) / (document_frequency.max(1) as f64))
+            .ln()
+            .max(0.0)
+    }
+
+    fn tfidf_vector(&self, text: &str) -> HashMap<String, f64> {
+        let tokens = tokenize(text);
+        let token_count = tokens.len() as f64;
+
+        let mut term_counts: HashMap<String, usize> = HashMap::new();
+        for token in &tokens {
+            *term_counts.entry(token.clone()).or_insert(0) += 1;
+        }
+
+        term_counts
+            .into_iter()
+            .map(|(token, count)| {
+                let tf = count as f64 / token_count;
+                let weight = tf * self.idf(&token);
+                (token, weight)
+            })
+            .collect()
+    }
+
+    /// TF-IDF cosine similarity between `a` and `b`, weighted by this table.
+    pub fn cosine(&self, a: &str, b: &str) -> f64 {
+        let vector_a = self.tfidf_vector(a);
+        let vector_b = self.tfidf_vector(b);
+
+        let dot_product: f64 = vector_a
+            .iter()
+            .filter_map(|(token, weight_a)| vector_b.get(token).map(|weight_b| weight_a * weight_b))
+            .sum();
+
+        let norm_a = vector_a
+            .values()
+            .map(|weight| weight * weight)
+            .sum::<f64>()
+            .sqrt();
+        let norm_b = vector_b
+            .values()
+            .map(|weight| weight * weight)
+            .sum::<f64>()
+            .sqrt();
+
+        if norm_a == 0.0 || norm_b == 0.0 {
+            return 0.0;
+        }
+
+        dot_product / (norm_a * norm_b)
+    }
+}